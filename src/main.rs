@@ -1,43 +1,189 @@
 use clap::Parser;
-use rand::{seq::SliceRandom, thread_rng};
+use rand::{thread_rng, Rng};
 use rayon::prelude::*;
 use std::{
+    collections::HashMap,
     fs::File,
     io::{BufRead, BufReader},
     num::ParseIntError,
     path::PathBuf,
 };
 
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum Algo {
+    Simple,
+    KargerStein,
+}
+
 #[derive(Parser, Debug)]
 struct Args {
     #[arg(short, long, value_delimiter = ',', num_args = 1..)]
     files: Vec<PathBuf>,
     #[arg(short, long)]
-    iters: u32,
+    iters: Option<u32>,
+    #[arg(long, value_enum, default_value = "simple")]
+    algo: Algo,
+    #[arg(long)]
+    exact: bool,
+    #[arg(long)]
+    emit_partition: bool,
+    #[arg(long)]
+    output: Option<PathBuf>,
+    // Target failure probability: pick the smallest iteration count that finds a true min cut
+    // with probability at least 1 - confidence. Takes priority over --iters when given.
+    #[arg(long)]
+    confidence: Option<f64>,
 }
 
+// An edge is (u, v, weight). Unweighted inputs default every edge's weight to 1.0.
+type Edge = (usize, usize, f64);
+// Edges, vertex count, and (for adjacency-list input) the index -> original label table.
+type ParsedInput = (Vec<Edge>, usize, Option<Vec<String>>);
+
 #[derive(Debug, Clone)]
 struct Node {
     comps: Vec<usize>,
     edges: Vec<usize>,
 }
 
-fn read_input(input: &PathBuf) -> Result<(Vec<(usize, usize)>, usize), ParseIntError> {
-    let mut edge_list = Vec::new();
+// A Fenwick (binary indexed) tree over live edge weights, used to sample the next edge to
+// contract with probability proportional to its weight in O(log m).
+struct Fenwick {
+    tree: Vec<f64>,
+}
+
+impl Fenwick {
+    fn new(weights: &[f64]) -> Self {
+        let mut fenwick = Fenwick {
+            tree: vec![0.0; weights.len() + 1],
+        };
+        for (i, &w) in weights.iter().enumerate() {
+            fenwick.add(i, w);
+        }
+        fenwick
+    }
+
+    fn add(&mut self, i: usize, delta: f64) {
+        let mut i = i + 1;
+        while i < self.tree.len() {
+            self.tree[i] += delta;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    fn total(&self) -> f64 {
+        let mut sum = 0.0;
+        let mut i = self.tree.len() - 1;
+        while i > 0 {
+            sum += self.tree[i];
+            i -= i & i.wrapping_neg();
+        }
+        sum
+    }
+
+    // Binary-searches for the index whose cumulative weight first exceeds `target`.
+    fn find(&self, mut target: f64) -> usize {
+        let m = self.tree.len() - 1;
+        let mut pos = 0;
+        // Largest power of two <= m, the standard starting stride for a Fenwick descent
+        let mut step = 1;
+        while step <= m {
+            step <<= 1;
+        }
+        step >>= 1;
+        while step > 0 {
+            let next = pos + step;
+            if next <= m && self.tree[next] <= target {
+                pos = next;
+                target -= self.tree[next];
+            }
+            step >>= 1;
+        }
+        pos
+    }
+}
+
+// Reads either plain "u v [weight]" edge-pair lines, or - if any line contains a colon - an
+// adjacency list of "label: nbr1 nbr2 ..." lines with arbitrary string labels. In the adjacency
+// case the returned label table lets the partition-emitting output print original names instead
+// of interned indices.
+fn read_input(input: &PathBuf) -> Result<ParsedInput, ParseIntError> {
     let file = File::open(input).expect("Failed to open input file");
+    let lines = BufReader::new(file)
+        .lines()
+        .map(|line| line.expect("Failed to read line"))
+        .collect::<Vec<_>>();
+
+    if lines.iter().any(|line| line.contains(':')) {
+        let (edges, n, labels) = read_adjacency_list(&lines);
+        Ok((edges, n, Some(labels)))
+    } else {
+        read_edge_pairs(&lines).map(|(edges, n)| (edges, n, None))
+    }
+}
+
+fn read_edge_pairs(lines: &[String]) -> Result<(Vec<Edge>, usize), ParseIntError> {
+    let mut edge_list = Vec::new();
     let mut max_idx = 0;
-    for line in BufReader::new(file).lines() {
-        let line = line.expect("Failed to read line");
+    for line in lines {
         let parts = line.split_whitespace().collect::<Vec<_>>();
-        assert_eq!(parts.len(), 2, "Expected two numbers per line");
-        let edge = (parts[0].parse::<usize>()?, parts[1].parse::<usize>()?);
-        edge_list.push(edge);
+        assert!(
+            parts.len() == 2 || parts.len() == 3,
+            "Expected two numbers per line, optionally followed by a weight"
+        );
+        let weight = if parts.len() == 3 {
+            parts[2].parse::<f64>().expect("Failed to parse weight")
+        } else {
+            1.0
+        };
+        let edge = (parts[0].parse::<usize>()?, parts[1].parse::<usize>()?, weight);
         max_idx = usize::max(max_idx, usize::max(edge.0, edge.1));
+        edge_list.push(edge);
     }
     Ok((edge_list, max_idx + 1))
 }
 
-fn simple_cut(edges: &[(usize, usize)], n: usize) -> [Node; 2] {
+// Interns `label` into a contiguous index, recording it in `labels` the first time it is seen.
+fn intern(label: &str, index_of: &mut HashMap<String, usize>, labels: &mut Vec<String>) -> usize {
+    *index_of.entry(label.to_string()).or_insert_with(|| {
+        labels.push(label.to_string());
+        labels.len() - 1
+    })
+}
+
+fn read_adjacency_list(lines: &[String]) -> (Vec<Edge>, usize, Vec<String>) {
+    let mut index_of = HashMap::new();
+    let mut labels = Vec::new();
+    // Tracks (min, max) vertex pairs already turned into an edge, so "a b" and "b a" across
+    // different lines aren't double-counted
+    let mut seen_edges = std::collections::HashSet::new();
+    let mut edge_list = Vec::new();
+
+    for line in lines {
+        let (src, neighbours) = line
+            .split_once(':')
+            .expect("Expected 'label: neighbours' line");
+        let u = intern(src.trim(), &mut index_of, &mut labels);
+        for nbr in neighbours.split_whitespace() {
+            let v = intern(nbr, &mut index_of, &mut labels);
+            if u == v {
+                // A node listing itself as its own neighbour isn't a real edge - skip it
+                continue;
+            }
+            let key = (u.min(v), u.max(v));
+            if seen_edges.insert(key) {
+                edge_list.push((key.0, key.1, 1.0));
+            }
+        }
+    }
+
+    let n = labels.len();
+    (edge_list, n, labels)
+}
+
+// Builds the initial per-vertex bookkeeping: each node starts as its own component and knows
+// which edges touch it.
+fn build_nodes(edges: &[Edge], n: usize) -> Vec<Node> {
     let mut nodes = vec![
         Node {
             comps: Vec::new(), // Contains nodes that were merged together
@@ -50,11 +196,18 @@ fn simple_cut(edges: &[(usize, usize)], n: usize) -> [Node; 2] {
         node.comps = vec![i];
     });
     // Fill edges of each node with the index of the edges connected to it
-    let mut edges = edges.to_vec();
-    for (i, (u, v)) in edges.iter().enumerate() {
+    for (i, (u, v, _)) in edges.iter().enumerate() {
         nodes[*u].edges.push(i);
         nodes[*v].edges.push(i);
     }
+    nodes
+}
+
+// Randomly contracts the given (super)graph, sampling each next edge with probability
+// proportional to its current weight, until only `target` supernodes remain. Returns the
+// resulting supernodes together with the surviving edges remapped to index into them, so the
+// result can be fed back into `contract_to` for further contraction (as Karger-Stein does).
+fn contract_to(mut nodes: Vec<Node>, edges: &[Edge], target: usize) -> (Vec<Node>, Vec<Edge>) {
     // Clear isolated nodes
     for node in &mut nodes {
         if node.edges.is_empty() {
@@ -62,26 +215,17 @@ fn simple_cut(edges: &[(usize, usize)], n: usize) -> [Node; 2] {
         }
     }
 
-    // Shuffle the edges to avoid having to use random at each iteration
-    let mut shuffled = (0..edges.len()).collect::<Vec<usize>>();
-    shuffled.shuffle(&mut thread_rng());
-    // remaining[i] is true if the edge i is still in the graph
-    let mut remaining = vec![true; edges.len()];
+    let mut edges = edges.to_vec();
+    let mut fenwick = Fenwick::new(&edges.iter().map(|e| e.2).collect::<Vec<_>>());
 
     // nodes vector also contains isolated nodes, we need to count only the nodes with edges
     let mut remaining_nodes = nodes.iter().filter(|n| !n.comps.is_empty()).count();
-    let mut index = 0;
-    // While there are more than 2 nodes
-    while remaining_nodes > 2 {
-        let edge_index = shuffled[index];
-        index += 1;
-
-        if !remaining[edge_index] {
-            continue;
-        }
+    // While there are more supernodes than the target
+    while remaining_nodes > target {
+        // Sample the next edge with probability proportional to its live weight
+        let edge_index = fenwick.find(thread_rng().gen_range(0.0..fenwick.total()));
 
-        // Get "random" edge
-        let (mut u, mut v) = edges[edge_index];
+        let (mut u, mut v, _) = edges[edge_index];
 
         // To optimize edge remapping we merge the node with less edges into the one with more edges
         if nodes[u].edges.len() < nodes[v].edges.len() {
@@ -90,9 +234,8 @@ fn simple_cut(edges: &[(usize, usize)], n: usize) -> [Node; 2] {
         assert!(u != v, "Self-loop detected");
         let comps_v = nodes[v].comps.clone();
         let edges_v = nodes[v].edges.clone();
-        // Add the components and edges of v to u
+        // Add the components of v to u
         nodes[u].comps.extend(&comps_v);
-        nodes[u].edges.extend(&edges_v);
         // Remove those edges from the "new" node that connect between the old two nodes (to avoid
         // self-loops)
         nodes[u].edges.retain(|&x| {
@@ -100,23 +243,28 @@ fn simple_cut(edges: &[(usize, usize)], n: usize) -> [Node; 2] {
         });
         remaining_nodes -= 1;
 
-        // Update the edges of v to point to u instead of v
+        // Update the edges of v to point to u instead of v. Parallel edges between u and the same
+        // neighbour are deliberately left as separate live entries rather than folded together:
+        // once an edge is merged away it stops being tracked in its surviving endpoint's edge
+        // list, so if that endpoint is itself contracted away later, the merged-in edge's other
+        // (frozen) endpoint can never be updated again and would dangle.
         for &i in &edges_v {
-            if !((edges[i].0 == u && edges[i].1 == v) || (edges[i].0 == v && edges[i].1 == u)) {
-                // If the edge is not between the two nodes we are merging, we need to update it
-                // (change v to u)
-                if edges[i].0 == v {
-                    edges[i].0 = u;
-                } else {
-                    edges[i].1 = u;
-                }
-                // This should never happen but I'm paranoid
-                debug_assert!(edges[i].0 != edges[i].1, "Self-loop detected");
+            if (edges[i].0 == u && edges[i].1 == v) || (edges[i].0 == v && edges[i].1 == u) {
+                // This is the edge between the two nodes we just merged - drop its weight
+                fenwick.add(i, -edges[i].2);
+                edges[i].2 = 0.0;
+                continue;
+            }
+            // If the edge is not between the two nodes we are merging, we need to update it
+            // (change v to u)
+            if edges[i].0 == v {
+                edges[i].0 = u;
             } else {
-                // This is the same condition as with nodes.edges.retain - here we mark the edge
-                // between the two nodes as removed
-                remaining[i] = false;
+                edges[i].1 = u;
             }
+            // This should never happen but I'm paranoid
+            debug_assert!(edges[i].0 != edges[i].1, "Self-loop detected");
+            nodes[u].edges.push(i);
         }
         // Clear the components and edges of v to save memory
         // This also marks the node as removed
@@ -124,17 +272,87 @@ fn simple_cut(edges: &[(usize, usize)], n: usize) -> [Node; 2] {
         nodes[v].edges.clear();
     }
 
-    // Remove all isolated (removed) nodes from list
+    // Keep only the edges that still carry weight (the rest became self-loops when their two
+    // endpoints were contracted together)
+    let live_edges = edges
+        .iter()
+        .filter(|e| e.2 > 0.0)
+        .copied()
+        .collect::<Vec<_>>();
+
+    // Remap the surviving nodes (and the edges between them) to contiguous indices, so the result
+    // can be contracted further as if it were a fresh graph
+    let mut remap = vec![usize::MAX; nodes.len()];
+    let mut next = 0;
+    for (i, node) in nodes.iter().enumerate() {
+        if !node.comps.is_empty() {
+            remap[i] = next;
+            next += 1;
+        }
+    }
     nodes.retain(|n| !n.comps.is_empty());
+    let remapped_edges = live_edges
+        .iter()
+        .map(|&(u, v, w)| (remap[u], remap[v], w))
+        .collect::<Vec<_>>();
+    for node in &mut nodes {
+        node.edges.clear();
+    }
+    for (i, &(u, v, _)) in remapped_edges.iter().enumerate() {
+        nodes[u].edges.push(i);
+        nodes[v].edges.push(i);
+    }
+
+    (nodes, remapped_edges)
+}
 
+fn simple_cut(edges: &[Edge], n: usize) -> [Node; 2] {
+    let nodes = build_nodes(edges, n);
+    let (nodes, _) = contract_to(nodes, edges, 2);
     // This should always be true
     assert_eq!(nodes.len(), 2);
-
     [nodes[0].clone(), nodes[1].clone()]
 }
 
-fn get_cut_size(cut: &[Node; 2], edges: &[(usize, usize)], n: usize) -> usize {
-    // Each node gets assigned 1 if it is in the first partition or 2 if it is in the second
+// Karger-Stein recursive contraction: each contraction down to `t = ceil(1 + n/sqrt(2))`
+// supernodes preserves a fixed min cut with probability >= 1/2, so recursing on two independent
+// copies and keeping the smaller of the two results succeeds with probability Theta(1/log n),
+// far better than a single flat contraction's ~2/n^2.
+fn karger_stein(edges: &[Edge], n: usize) -> [Node; 2] {
+    let nodes = build_nodes(edges, n);
+    karger_stein_rec(nodes, edges.to_vec(), n, edges, n)
+}
+
+fn karger_stein_rec(
+    nodes: Vec<Node>,
+    local_edges: Vec<Edge>,
+    local_n: usize,
+    orig_edges: &[Edge],
+    orig_n: usize,
+) -> [Node; 2] {
+    if local_n <= 6 {
+        let (nodes, _) = contract_to(nodes, &local_edges, 2);
+        assert_eq!(nodes.len(), 2);
+        return [nodes[0].clone(), nodes[1].clone()];
+    }
+
+    let t = (1.0 + local_n as f64 / 2f64.sqrt()).ceil() as usize;
+    let (nodes1, edges1) = contract_to(nodes.clone(), &local_edges, t);
+    let (nodes2, edges2) = contract_to(nodes, &local_edges, t);
+
+    let cut1 = karger_stein_rec(nodes1, edges1, t, orig_edges, orig_n);
+    let cut2 = karger_stein_rec(nodes2, edges2, t, orig_edges, orig_n);
+
+    if get_cut_size(&cut1, orig_edges, orig_n) <= get_cut_size(&cut2, orig_edges, orig_n) {
+        cut1
+    } else {
+        cut2
+    }
+}
+
+// Each node gets assigned 1 if it is in the first partition or 2 if it is in the second, and the
+// weight of every crossing edge is summed into the cut size.
+fn partition_and_cut_size(cut: &[Node; 2], edges: &[Edge], n: usize) -> (Vec<usize>, f64) {
     let mut partition = vec![0; n];
     for (i, node) in cut.iter().enumerate() {
         for &comp in &node.comps {
@@ -144,54 +362,342 @@ fn get_cut_size(cut: &[Node; 2], edges: &[(usize, usize)], n: usize) -> usize {
         }
     }
 
-    let mut cut_size = 0;
-    for (u, v) in edges {
-        // Increment cut size if the nodes are in different partitions
+    let mut cut_size = 0.0;
+    for (u, v, w) in edges {
+        // Add the edge's weight if the nodes are in different partitions
         if partition[*u] != partition[*v] {
-            cut_size += 1;
+            cut_size += w;
         }
     }
 
-    cut_size
+    (partition, cut_size)
+}
+
+fn get_cut_size(cut: &[Node; 2], edges: &[Edge], n: usize) -> f64 {
+    partition_and_cut_size(cut, edges, n).1
+}
+
+// Writes out the two vertex sets of the best cut found and the product of their sizes - supports
+// the common "cut these edges, multiply the group sizes" use case. Vertices are printed by their
+// original name when the input used the adjacency-list format, otherwise by their index.
+fn emit_partition(
+    cut: &[Node; 2],
+    partition: &[usize],
+    labels: &Option<Vec<String>>,
+    output: &Option<PathBuf>,
+) {
+    let name = |i: usize| {
+        labels
+            .as_ref()
+            .map(|labels| labels[i].clone())
+            .unwrap_or_else(|| i.to_string())
+    };
+    let side = |label: usize| {
+        partition
+            .iter()
+            .enumerate()
+            .filter(|&(_, &p)| p == label)
+            .map(|(i, _)| name(i))
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    let size1 = cut[0].comps.len();
+    let size2 = cut[1].comps.len();
+    let text = format!("{}\n{}\n{}\n", side(1), side(2), size1 * size2);
+
+    match output {
+        Some(path) => std::fs::write(path, text).expect("Failed to write partition output"),
+        None => print!("{text}"),
+    }
+}
+
+// When --emit-partition is combined with multiple --files, writing every partition to the same
+// --output path would silently clobber all but the last one. Derive a distinct path per input by
+// splicing the input's file stem into the output file name (out.txt, g1.txt -> out-g1.txt).
+fn per_input_output_path(output: &std::path::Path, input: &std::path::Path) -> PathBuf {
+    let input_stem = input
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("input");
+    let mut file_name = output
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("output")
+        .to_string();
+    file_name.push('-');
+    file_name.push_str(input_stem);
+    if let Some(ext) = output.extension().and_then(|e| e.to_str()) {
+        file_name.push('.');
+        file_name.push_str(ext);
+    }
+    output.with_file_name(file_name)
+}
+
+// Exact global min cut via Stoer-Wagner. Builds a dense symmetric weight matrix and runs n-1
+// minimum-cut phases: each phase grows a set A from an arbitrary vertex by repeatedly adding the
+// vertex most tightly connected to A, and the connectivity of the last vertex added ("cut of the
+// phase") is a candidate global min cut. The last two vertices added are then merged and the next
+// phase repeats on the smaller graph. O(V^3) overall, which is fine for validation-sized inputs.
+fn stoer_wagner(edges: &[Edge], n: usize) -> f64 {
+    let mut w = vec![vec![0.0; n]; n];
+    for &(u, v, weight) in edges {
+        w[u][v] += weight;
+        w[v][u] += weight;
+    }
+
+    let mut active = (0..n).collect::<Vec<usize>>();
+    let mut best = f64::INFINITY;
+
+    while active.len() > 1 {
+        let mut in_a = vec![false; n];
+        let mut weights = vec![0.0; n];
+
+        let first = active[0];
+        in_a[first] = true;
+        for &v in &active {
+            weights[v] = w[first][v];
+        }
+
+        let mut s = first;
+        let mut t = first;
+        let mut cut_of_phase = 0.0;
+        for step in 1..active.len() {
+            // Add the vertex not yet in A with the largest total connectivity to A
+            let mut z = usize::MAX;
+            let mut best_weight = -1.0;
+            for &v in &active {
+                if !in_a[v] && weights[v] > best_weight {
+                    best_weight = weights[v];
+                    z = v;
+                }
+            }
+            in_a[z] = true;
+
+            if step == active.len() - 1 {
+                // The last two vertices added are s (second-to-last) and t (last)
+                s = t;
+                t = z;
+                cut_of_phase = best_weight;
+            } else {
+                t = z;
+            }
+
+            for &v in &active {
+                if !in_a[v] {
+                    weights[v] += w[z][v];
+                }
+            }
+        }
+
+        best = best.min(cut_of_phase);
+
+        // Merge s and t: fold t's weights into s and drop t from the active set
+        for &v in &active {
+            if v != s && v != t {
+                w[s][v] += w[t][v];
+                w[v][s] += w[v][t];
+            }
+        }
+        active.retain(|&v| v != t);
+    }
+
+    best
+}
+
+// Number of independent randomized runs needed to find a true min cut with probability at least
+// 1 - delta. A single plain-Karger run succeeds with probability >= 2/(n(n-1)), so R runs fail
+// with probability <= (1 - 2/(n(n-1)))^R; Karger-Stein substitutes its Theta(1/ln n) per-call
+// success probability into the same bound. Both are solved for the smallest R satisfying
+// R >= ln(delta) / ln(1 - p).
+fn iters_for_confidence(delta: f64, n: usize, algo: &Algo) -> u32 {
+    let p = match algo {
+        Algo::Simple => 2.0 / (n as f64 * (n as f64 - 1.0)),
+        Algo::KargerStein => 1.0 / (n as f64).ln(),
+    };
+    // For small n the per-call success probability can reach or exceed 1 (e.g. n <= 2 for
+    // Simple, or n small enough that 1/ln(n) >= 1 for Karger-Stein). A single run is then
+    // already guaranteed to find the true min cut, so clamp instead of letting (1.0 - p).ln()
+    // go non-positive and produce NaN.
+    if !p.is_finite() || p >= 1.0 {
+        return 1;
+    }
+    let r = (delta.ln() / (1.0 - p).ln()).ceil();
+    r.max(1.0) as u32
 }
 
 fn main() {
     let args = Args::parse();
-    println!("|            name |          (n, m) |       opt | avg. runs |");
-    println!("|-----------------|-----------------|-----------|-----------|");
+    if args.exact {
+        println!("|            name |          (n, m) |       opt | opt_exact | iters_to_opt | avg. runs |");
+        println!("|-----------------|-----------------|-----------|-----------|---------------|-----------|");
+    } else {
+        println!("|            name |          (n, m) |       opt | avg. runs |");
+        println!("|-----------------|-----------------|-----------|-----------|");
+    }
     for input in &args.files {
-        let (edges, n) = read_input(input).expect("Failed to read input file");
+        let (edges, n, labels) = read_input(input).expect("Failed to read input file");
+
+        // Either use the requested iteration count directly, or derive the smallest one that
+        // meets the requested failure probability
+        let iters = match args.confidence {
+            Some(delta) => {
+                let r = iters_for_confidence(delta, n, &args.algo);
+                println!(
+                    "Using {r} iterations to reach a true min cut with probability >= {:.4}",
+                    1.0 - delta
+                );
+                r
+            }
+            None => args
+                .iters
+                .expect("Either --iters or --confidence must be given"),
+        };
 
-        // Get cut size for each iteration
-        let cuts = (0..args.iters as usize)
+        // Get the cut and its size for each iteration
+        let cuts = (0..iters as usize)
             .into_par_iter()
             .map(|_| {
-                let cut = simple_cut(&edges, n);
-                get_cut_size(&cut, &edges, n)
+                let cut = match args.algo {
+                    Algo::Simple => simple_cut(&edges, n),
+                    Algo::KargerStein => karger_stein(&edges, n),
+                };
+                let size = get_cut_size(&cut, &edges, n);
+                (cut, size)
             })
-            .collect::<Vec<usize>>();
+            .collect::<Vec<(_, f64)>>();
 
         // Get minimum
-        let min_cut_size = *cuts.iter().min().unwrap();
+        let min_cut_size = cuts.iter().map(|(_, size)| *size).fold(f64::INFINITY, f64::min);
 
         // Simulate sequential runs to find out how many iterations it takes from the last min cut
         // to the next one
-        let mut runs = Vec::with_capacity(args.iters as usize);
+        let mut runs = Vec::with_capacity(iters as usize);
         let mut last_run = 0;
-        for (i, cut) in cuts.iter().enumerate() {
-            if *cut == min_cut_size {
+        for (i, (_, size)) in cuts.iter().enumerate() {
+            if (*size - min_cut_size).abs() < 1e-9 {
                 runs.push(i + 1 - last_run);
                 last_run = i + 1;
             }
         }
         let avg_minimum = runs.iter().sum::<usize>() as f64 / runs.len() as f64;
 
-        println!(
-            "|{:>16} | {:>15} |{:10} |{:10.2} |",
-            input.file_name().unwrap().to_str().unwrap(),
-            format!("({},{})", n, edges.len()),
-            min_cut_size,
-            avg_minimum
+        if args.emit_partition {
+            let (best_cut, _) = cuts
+                .iter()
+                .find(|(_, size)| (*size - min_cut_size).abs() < 1e-9)
+                .expect("At least one cut was computed");
+            let (partition, _) = partition_and_cut_size(best_cut, &edges, n);
+            // Multiple inputs writing to the same --output path would otherwise clobber each
+            // other, so give each one a distinct, input-derived path.
+            let output = args.output.as_ref().map(|o| {
+                if args.files.len() > 1 {
+                    per_input_output_path(o, input)
+                } else {
+                    o.clone()
+                }
+            });
+            emit_partition(best_cut, &partition, &labels, &output);
+        }
+
+        if args.exact {
+            let opt_exact = stoer_wagner(&edges, n);
+            // How many random iterations it actually took to reach the true optimum
+            let iters_to_opt = cuts
+                .iter()
+                .position(|(_, size)| (*size - opt_exact).abs() < 1e-9)
+                .map(|i| (i + 1).to_string())
+                .unwrap_or_else(|| "n/a".to_string());
+            println!(
+                "|{:>16} | {:>15} |{:10.2} |{:10.2} |{:>14} |{:10.2} |",
+                input.file_name().unwrap().to_str().unwrap(),
+                format!("({},{})", n, edges.len()),
+                min_cut_size,
+                opt_exact,
+                iters_to_opt,
+                avg_minimum
+            );
+        } else {
+            println!(
+                "|{:>16} | {:>15} |{:10.2} |{:10.2} |",
+                input.file_name().unwrap().to_str().unwrap(),
+                format!("({},{})", n, edges.len()),
+                min_cut_size,
+                avg_minimum
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two triangles (0,1,2) and (3,4,5) joined by a single bridge edge 2-3.
+    // The only min cut is the bridge itself, weight 1.0.
+    fn two_triangles() -> (Vec<Edge>, usize) {
+        let edges = vec![
+            (0, 1, 1.0),
+            (1, 2, 1.0),
+            (0, 2, 1.0),
+            (2, 3, 1.0),
+            (3, 4, 1.0),
+            (4, 5, 1.0),
+            (3, 5, 1.0),
+        ];
+        (edges, 6)
+    }
+
+    #[test]
+    fn stoer_wagner_matches_known_min_cut() {
+        let (edges, n) = two_triangles();
+        assert!((stoer_wagner(&edges, n) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn simple_cut_finds_the_exact_min_cut() {
+        let (edges, n) = two_triangles();
+        let exact = stoer_wagner(&edges, n);
+        let min_found = (0..200)
+            .map(|_| get_cut_size(&simple_cut(&edges, n), &edges, n))
+            .fold(f64::INFINITY, f64::min);
+        assert!((min_found - exact).abs() < 1e-9);
+    }
+
+    #[test]
+    fn karger_stein_finds_the_exact_min_cut() {
+        let (edges, n) = two_triangles();
+        let exact = stoer_wagner(&edges, n);
+        let min_found = (0..50)
+            .map(|_| get_cut_size(&karger_stein(&edges, n), &edges, n))
+            .fold(f64::INFINITY, f64::min);
+        assert!((min_found - exact).abs() < 1e-9);
+    }
+
+    // "a: a b" has a self-referencing neighbour, and the reciprocal "a b" / "b a" pair across
+    // the "a" and "b" lines should only produce one edge, not two.
+    #[test]
+    fn read_input_skips_self_loops_and_dedups_reciprocal_edges() {
+        let path = std::env::temp_dir().join("min_cut_eval_adjacency_list_test.txt");
+        std::fs::write(&path, "a: a b\nb: a c\nc: b\n").unwrap();
+        let (edges, n, labels) = read_input(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let labels = labels.expect("adjacency-list input should return a label table");
+        assert_eq!(n, 3);
+        assert_eq!(labels, vec!["a", "b", "c"]);
+
+        let name_of = |i: usize| labels[i].clone();
+        let mut pairs = edges
+            .iter()
+            .map(|&(u, v, w)| (name_of(u), name_of(v), w))
+            .collect::<Vec<_>>();
+        pairs.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        assert_eq!(
+            pairs,
+            vec![
+                ("a".to_string(), "b".to_string(), 1.0),
+                ("b".to_string(), "c".to_string(), 1.0),
+            ]
         );
     }
 }